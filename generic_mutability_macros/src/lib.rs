@@ -0,0 +1,235 @@
+//! Companion proc-macro crate for `generic_mutability_test`.
+//!
+//! Hand-writing the `impl<T: ProbablyMutable>` / `impl Mutable` / `impl Immutable`
+//! triad for every type is exactly the boilerplate the parent crate's module docs
+//! complain about. [`generic_mutability`] collapses that triad back into a single
+//! `impl` block: write every method once, mark the ones that need a concrete
+//! mutability with `#[mutable]` or `#[immutable]`, and the attribute fans them out
+//! into the three blocks the borrow checker actually wants to see.
+//!
+//! ```ignore
+//! #[generic_mutability]
+//! impl<'a, T: ProbablyMutable> Window<'a, T> {
+//!     fn get_id(&self) -> usize { self.id }
+//!
+//!     #[mutable]
+//!     fn set_name(&mut self, name: impl Into<String>) { self.name = name.into(); }
+//!
+//!     #[immutable]
+//!     fn get_frame(&self, id: usize) -> Option<Frame<'a, Immutable>> { todo!() }
+//! }
+//! ```
+//!
+//! expands to the three `impl` blocks, unmarked methods landing in the generic one.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::visit_mut::{self, VisitMut};
+use syn::{
+    parse_macro_input, punctuated::Punctuated, FnArg, GenericArgument, GenericParam, Ident,
+    ImplItem, ImplItemFn, ItemImpl, Path, PathArguments, Token, Type, TypePath, WhereClause,
+};
+
+/// Partitions the methods of a single `impl` block by their `#[mutable]` /
+/// `#[immutable]` marker and re-emits the usual three blocks.
+#[proc_macro_attribute]
+pub fn generic_mutability(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as ItemImpl);
+
+    let self_ty = match *input.self_ty.clone() {
+        Type::Path(path) => path,
+        other => {
+            return syn::Error::new_spanned(other, "expected a plain `Type<'a, T>` self type")
+                .to_compile_error()
+                .into()
+        }
+    };
+
+    let (base_path, lifetimes, mutability_param) = match split_self_ty(&self_ty) {
+        Ok(parts) => parts,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let mut generic_items = Vec::new();
+    let mut mutable_items = Vec::new();
+    let mut immutable_items = Vec::new();
+
+    for impl_item in input.items {
+        let ImplItem::Fn(mut method) = impl_item else {
+            generic_items.push(impl_item);
+            continue;
+        };
+
+        match take_marker(&mut method) {
+            Ok(Some(Marker::Mutable)) => {
+                if let Err(err) = require_mut_self(&method) {
+                    return err.to_compile_error().into();
+                }
+                mutable_items.push(ImplItem::Fn(method));
+            }
+            Ok(Some(Marker::Immutable)) => immutable_items.push(ImplItem::Fn(method)),
+            Ok(None) => generic_items.push(ImplItem::Fn(method)),
+            Err(err) => return err.to_compile_error().into(),
+        }
+    }
+
+    let generics = &input.generics;
+    let (impl_generics, _ty_generics, where_clause) = generics.split_for_impl();
+    let lifetime_generics: Punctuated<GenericParam, Token![,]> = lifetimes
+        .iter()
+        .cloned()
+        .map(|lt| GenericParam::Lifetime(syn::LifetimeParam::new(lt)))
+        .collect();
+    let lifetime_args: Punctuated<syn::Lifetime, Token![,]> = lifetimes.iter().cloned().collect();
+
+    // The generic block keeps the where-clause verbatim (it's still generic over
+    // `#mutability_param`), but the Mutable/Immutable blocks fix that parameter to
+    // a concrete type, so any predicate mentioning it has to be rewritten in terms
+    // of `Mutable`/`Immutable` instead of just dropped, or a bound load-bearing for
+    // those concrete impls (e.g. an outlives bound between two of the lifetimes)
+    // would silently stop applying to them.
+    let mutable_where = substitute_marker(where_clause.cloned(), &mutability_param, "Mutable");
+    let immutable_where = substitute_marker(where_clause.cloned(), &mutability_param, "Immutable");
+
+    let generic_ty = quote!(#base_path<#lifetime_args, #mutability_param>);
+    let mutable_ty = quote!(#base_path<#lifetime_args, Mutable>);
+    let immutable_ty = quote!(#base_path<#lifetime_args, Immutable>);
+
+    let expanded = quote! {
+        impl #impl_generics #generic_ty #where_clause {
+            #(#generic_items)*
+        }
+        impl<#lifetime_generics> #mutable_ty #mutable_where {
+            #(#mutable_items)*
+        }
+        impl<#lifetime_generics> #immutable_ty #immutable_where {
+            #(#immutable_items)*
+        }
+    };
+
+    expanded.into()
+}
+
+/// Rewrites a where-clause's predicates for a concrete-marker block, replacing
+/// every bare occurrence of the mutability type param (e.g. `T`) with the
+/// literal `Mutable`/`Immutable` ident, so bounds on it (or on a lifetime that
+/// relates to it) still apply once the param itself is gone from the impl's
+/// own generics.
+fn substitute_marker(
+    where_clause: Option<WhereClause>,
+    marker: &Ident,
+    replacement: &str,
+) -> Option<WhereClause> {
+    let mut where_clause = where_clause?;
+    let replacement: Type = syn::parse_str(replacement).expect("valid type ident");
+    let mut visitor = MarkerSubstitution { marker, replacement };
+    visitor.visit_where_clause_mut(&mut where_clause);
+    Some(where_clause)
+}
+
+struct MarkerSubstitution<'a> {
+    marker: &'a Ident,
+    replacement: Type,
+}
+
+impl VisitMut for MarkerSubstitution<'_> {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        if let Type::Path(path) = ty {
+            if path.qself.is_none() && path.path.is_ident(self.marker) {
+                *ty = self.replacement.clone();
+                return;
+            }
+        }
+        visit_mut::visit_type_mut(self, ty);
+    }
+}
+
+enum Marker {
+    Mutable,
+    Immutable,
+}
+
+/// Strips and classifies the `#[mutable]` / `#[immutable]` marker attribute, if any.
+fn take_marker(method: &mut ImplItemFn) -> syn::Result<Option<Marker>> {
+    let mut marker = None;
+    let mut kept = Vec::new();
+
+    for attr in method.attrs.drain(..) {
+        if attr.path().is_ident("mutable") {
+            if marker.is_some() {
+                return Err(syn::Error::new_spanned(attr, "duplicate mutability marker"));
+            }
+            marker = Some(Marker::Mutable);
+        } else if attr.path().is_ident("immutable") {
+            if marker.is_some() {
+                return Err(syn::Error::new_spanned(attr, "duplicate mutability marker"));
+            }
+            marker = Some(Marker::Immutable);
+        } else {
+            kept.push(attr);
+        }
+    }
+
+    method.attrs = kept;
+    Ok(marker)
+}
+
+fn require_mut_self(method: &ImplItemFn) -> syn::Result<()> {
+    match method.sig.inputs.first() {
+        Some(FnArg::Receiver(receiver))
+            if receiver.reference.is_some() && receiver.mutability.is_some() =>
+        {
+            Ok(())
+        }
+        _ => Err(syn::Error::new_spanned(
+            &method.sig,
+            "#[mutable] methods must take `&mut self`",
+        )),
+    }
+}
+
+/// Pulls `(Ty, ['a, ...], T)` out of a `Ty<'a, ..., T>` self-type path. Types
+/// like [crate's `Frame`](../generic_mutability_test/struct.Frame.html) that
+/// thread a parent's own lifetime through alongside their own carry more than
+/// one lifetime argument, so every lifetime is collected, in order, rather
+/// than just the first or last.
+fn split_self_ty(
+    self_ty: &TypePath,
+) -> syn::Result<(Path, Vec<syn::Lifetime>, Ident)> {
+    let segment = self_ty
+        .path
+        .segments
+        .last()
+        .ok_or_else(|| syn::Error::new_spanned(self_ty, "expected a named self type"))?;
+
+    let mut base_path = self_ty.path.clone();
+    base_path.segments.last_mut().unwrap().arguments = PathArguments::None;
+
+    let mut lifetimes = Vec::new();
+    let mut mutability_param = None;
+
+    if let PathArguments::AngleBracketed(args) = &segment.arguments {
+        for arg in &args.args {
+            match arg {
+                GenericArgument::Lifetime(lt) => lifetimes.push(lt.clone()),
+                GenericArgument::Type(Type::Path(p)) if mutability_param.is_none() => {
+                    mutability_param = p.path.get_ident().cloned();
+                }
+                other => {
+                    return Err(syn::Error::new_spanned(
+                        other,
+                        "generic_mutability only supports a self type of the shape \
+                         `Ty<'a, ..., T: ProbablyMutable>` - lifetimes plus exactly one \
+                         mutability type param; no other type or const generics",
+                    ))
+                }
+            }
+        }
+    }
+
+    let mutability_param = mutability_param.ok_or_else(|| {
+        syn::Error::new_spanned(self_ty, "expected `Ty<'a, ..., T: ProbablyMutable>` self type")
+    })?;
+
+    Ok((base_path, lifetimes, mutability_param))
+}
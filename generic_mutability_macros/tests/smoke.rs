@@ -0,0 +1,87 @@
+//! End-to-end expansion smoke test. `generic_mutability_macros` is consumed by
+//! code outside this repo's own demo crate, so this exercises the macro
+//! directly against a small fixture instead of relying on `generic_mutability_test`
+//! happening to cover the same cases.
+
+use generic_mutability_macros::generic_mutability;
+
+trait ProbablyMutable {}
+struct Mutable;
+impl ProbablyMutable for Mutable {}
+struct Immutable;
+impl ProbablyMutable for Immutable {}
+
+struct Widget<'a, T: ProbablyMutable> {
+    name: &'a str,
+    _marker: std::marker::PhantomData<T>,
+}
+
+#[generic_mutability]
+impl<'a, T: ProbablyMutable> Widget<'a, T> {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    #[mutable]
+    fn shout(&mut self) -> String {
+        self.name.to_uppercase()
+    }
+
+    #[immutable]
+    fn whisper(&self) -> String {
+        self.name.to_lowercase()
+    }
+}
+
+#[test]
+fn fans_out_into_generic_mutable_and_immutable_blocks() {
+    let mut mutable: Widget<Mutable> = Widget {
+        name: "Hi",
+        _marker: std::marker::PhantomData,
+    };
+    assert_eq!(mutable.name(), "Hi");
+    assert_eq!(mutable.shout(), "HI");
+
+    let immutable: Widget<Immutable> = Widget {
+        name: "Hi",
+        _marker: std::marker::PhantomData,
+    };
+    assert_eq!(immutable.name(), "Hi");
+    assert_eq!(immutable.whisper(), "hi");
+}
+
+// Two lifetimes plus a where-clause bound (`'b: 'a`). The macro re-emits the
+// where-clause on the Mutable/Immutable blocks, and this checks that those
+// expanded blocks compile and their methods run. `'b: 'a` is also implied by
+// the `&'a &'b str` field, so the blocks would still compile if the clause
+// were dropped; this does not detect that case.
+struct Nested<'a, 'b, T: ProbablyMutable> {
+    inner: &'a &'b str,
+    _marker: std::marker::PhantomData<T>,
+}
+
+#[generic_mutability]
+impl<'a, 'b, T: ProbablyMutable> Nested<'a, 'b, T>
+where
+    'b: 'a,
+{
+    fn inner(&self) -> &'a str {
+        self.inner
+    }
+
+    #[mutable]
+    fn noop(&mut self) {}
+}
+
+#[test]
+fn propagates_where_clause_to_mutable_and_immutable_blocks() {
+    let s = String::from("hi");
+    let reference: &str = &s;
+    let double_reference: &&str = &reference;
+    let mut nested: Nested<Mutable> = Nested {
+        inner: double_reference,
+        _marker: std::marker::PhantomData,
+    };
+    assert_eq!(nested.inner(), "hi");
+    nested.noop();
+}
@@ -0,0 +1,144 @@
+//! An alternative, [RefCell]-backed `Root`/`Window` pair for callers who need
+//! several live child handles at once.
+//!
+//! The compile-time scheme in the rest of this crate forbids calling
+//! [crate::Root::make_child] twice in a row; that's correct, but sometimes too
+//! strict for a real GUI wrapper that wants to hold on to more than one child at
+//! a time. [CellRoot] hands out the same tri-state [ProbablyMutable] handles, but
+//! `Mutable`/`Immutable` is now enforced by `RefCell::borrow`/`borrow_mut` at
+//! runtime instead of by the borrow checker: an aliasing violation panics exactly
+//! like a plain `RefCell` would, rather than failing to compile.
+
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use crate::{Immutable, Mutable, ProbablyMutable};
+
+#[derive(Default)]
+struct WindowData {
+    name: String,
+}
+
+/// Interior-mutability counterpart of [crate::Root].
+#[derive(Default)]
+pub struct CellRoot {
+    windows: RefCell<HashMap<usize, Rc<RefCell<WindowData>>>>,
+    next_id: RefCell<usize>,
+}
+
+impl CellRoot {
+    pub fn new() -> Rc<Self> {
+        Rc::new(Self::default())
+    }
+
+    /// Creates a new child window and immediately checks out a [Mutable] handle
+    /// to it, same as [crate::Root::make_child].
+    pub fn make_child(self: &Rc<Self>) -> CellWindow<Mutable> {
+        let mut next_id = self.next_id.borrow_mut();
+        *next_id += 1;
+        let id = *next_id;
+        let data = Rc::new(RefCell::new(WindowData::default()));
+        self.windows.borrow_mut().insert(id, Rc::clone(&data));
+        CellWindow::<Mutable>::new(id, data)
+    }
+
+    /// Hands out an [Immutable] handle to an existing window. Unlike
+    /// [CellRoot::make_child], this can be called any number of times for the
+    /// same `id` at once, and coexists with other `Immutable` handles.
+    pub fn get_child(self: &Rc<Self>, id: usize) -> Option<CellWindow<Immutable>> {
+        let data = self.windows.borrow().get(&id).cloned()?;
+        Some(CellWindow::<Immutable>::new(id, data))
+    }
+}
+
+enum Guard {
+    Ref(Ref<'static, WindowData>),
+    Mut(RefMut<'static, WindowData>),
+}
+
+/// A runtime-checked alternative to [crate::Window].
+///
+/// Holding one of these is exactly like holding a live `Ref`/`RefMut`: the
+/// underlying `borrow`/`borrow_mut` happens at construction time and is kept for
+/// as long as the handle lives, so a conflicting handle for the same window
+/// panics instead of being rejected by the compiler.
+pub struct CellWindow<M: ProbablyMutable> {
+    id: usize,
+    // Field order matters here: `guard` borrows from `data` (through an unsafe
+    // lifetime erasure below, since a struct can't otherwise hold a `Ref`/`RefMut`
+    // next to the `RefCell` it was taken from), and fields drop in declaration
+    // order, so `guard` must be listed - and therefore dropped - before `data`.
+    guard: Guard,
+    // SAFETY: load-bearing, despite never being read directly - this `Rc` is what
+    // `guard`'s erased `'static` lifetime actually borrows from. Dropping this
+    // field (e.g. to silence an "unread field" lint) would let `data`'s `RefCell`
+    // be freed while `guard` still points into it, a use-after-free. It has to
+    // stay a *second* `Rc` (rather than e.g. reconstructing from `CellRoot`),
+    // since `CellRoot.windows` also holds one: both keep the same heap
+    // allocation, and therefore `guard`'s borrow, alive for as long as either
+    // handle exists.
+    #[allow(dead_code)]
+    data: Rc<RefCell<WindowData>>,
+    mutability: PhantomData<M>,
+}
+
+impl CellWindow<Mutable> {
+    fn new(id: usize, data: Rc<RefCell<WindowData>>) -> Self {
+        // Safety: `RefCell<WindowData>` lives on the heap behind this `Rc`, so
+        // its address is stable even if this `CellWindow` (or `data`, the `Rc`
+        // itself) is moved; erasing the borrow's lifetime to `'static` is sound
+        // as long as the `RefCell` outlives it. That's guaranteed by keeping a
+        // clone of the same `Rc` in `data` below, declared after (and therefore
+        // dropped after) `guard`, plus the original clone kept in
+        // `CellRoot.windows` for as long as this id stays registered.
+        let guard = Guard::Mut(unsafe {
+            std::mem::transmute::<RefMut<'_, WindowData>, RefMut<'static, WindowData>>(
+                data.borrow_mut(),
+            )
+        });
+        Self {
+            id,
+            guard,
+            data,
+            mutability: PhantomData,
+        }
+    }
+
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        let Guard::Mut(guard) = &mut self.guard else {
+            unreachable!("CellWindow<Mutable> always holds a Guard::Mut")
+        };
+        guard.name = name.into();
+    }
+}
+
+impl CellWindow<Immutable> {
+    fn new(id: usize, data: Rc<RefCell<WindowData>>) -> Self {
+        // Safety: see the `Mutable` constructor above - same heap-stable
+        // `RefCell`, same drop-order guarantee, same second `Rc` kept in `data`.
+        let guard = Guard::Ref(unsafe {
+            std::mem::transmute::<Ref<'_, WindowData>, Ref<'static, WindowData>>(data.borrow())
+        });
+        Self {
+            id,
+            guard,
+            data,
+            mutability: PhantomData,
+        }
+    }
+}
+
+impl<M: ProbablyMutable> CellWindow<M> {
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+
+    pub fn get_name(&self) -> &str {
+        match &self.guard {
+            Guard::Ref(r) => &r.name,
+            Guard::Mut(r) => &r.name,
+        }
+    }
+}
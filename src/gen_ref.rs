@@ -0,0 +1,161 @@
+//! A reusable generic-mutability smart pointer.
+//!
+//! Keeping a plain borrow of the parent alongside a `PhantomData<T>` marker loses
+//! the parent's own mutability the moment it's stored. [GenRef] factors that out
+//! into one type, used for the parent fields of [Window](crate::Window) and
+//! [Frame](crate::Frame),
+//! that actually carries a `&'a T` or `&'a mut T` depending on `M`, so a chain of
+//! parents can stay mutable all the way down instead of collapsing to `&'a Parent`
+//! at the first fold. It can also hold an owned value, for the rarer case where a
+//! projection (like downgrading `Mutable` to `Immutable`) has to produce a value of
+//! a different type than the one it was borrowed from, so there's no original
+//! borrow left to reuse.
+
+use std::ops::{Deref, DerefMut};
+
+use crate::{Immutable, Mutable, ProbablyMutable};
+
+enum Repr<'a, T> {
+    Ref(&'a T),
+    Mut(&'a mut T),
+    Owned(Box<T>),
+}
+
+/// Either a `&'a T` or a `&'a mut T` (or, for [Immutable] only, an owned `T`
+/// built by [GenRef::from_owned]), tagged by the same [ProbablyMutable] marker
+/// used throughout this crate.
+pub struct GenRef<'a, T, M: ProbablyMutable> {
+    repr: Repr<'a, T>,
+    mutability: std::marker::PhantomData<M>,
+}
+
+impl<'a, T> GenRef<'a, T, Immutable> {
+    pub fn from_ref(reference: &'a T) -> Self {
+        Self {
+            repr: Repr::Ref(reference),
+            mutability: std::marker::PhantomData,
+        }
+    }
+
+    /// Wraps a freshly-projected value (e.g. the result of some `as_immutable`
+    /// downgrade, which can't reuse the original borrow since it changes type)
+    /// instead of an existing borrow.
+    pub fn from_owned(value: T) -> Self {
+        Self {
+            repr: Repr::Owned(Box::new(value)),
+            mutability: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<'a, T> GenRef<'a, T, Mutable> {
+    pub fn from_mut(reference: &'a mut T) -> Self {
+        Self {
+            repr: Repr::Mut(reference),
+            mutability: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<'a, T, M: ProbablyMutable> GenRef<'a, T, M> {
+    /// Borrows out an [Immutable] view that reuses the same underlying reference,
+    /// the way [std::cell::RefMut::map] narrows a guard without re-fetching it.
+    pub fn reborrow(&self) -> GenRef<'_, T, Immutable> {
+        GenRef::from_ref(&**self)
+    }
+
+    /// Consumes the `GenRef`, keeping the same borrow but downgrading its marker,
+    /// so a `Mutable` handle can be turned into an `Immutable` one in place.
+    pub fn into_immutable(self) -> GenRef<'a, T, Immutable> {
+        match self.repr {
+            Repr::Ref(r) => GenRef::from_ref(r),
+            Repr::Mut(r) => GenRef::from_ref(r),
+            Repr::Owned(b) => GenRef::from_owned(*b),
+        }
+    }
+
+    /// Consumes the `GenRef`, handing back the wrapped reference with its
+    /// original `'a` intact. Unlike [Deref::deref], whose output is only ever
+    /// good for as long as the `GenRef` itself is borrowed, this lets a
+    /// self-consuming projection (like [crate::Frame::into_immutable]) build a
+    /// value from a borrow that outlives the `GenRef` doing the projecting.
+    pub fn into_ref(self) -> &'a T {
+        match self.repr {
+            Repr::Ref(r) => r,
+            Repr::Mut(r) => r,
+            Repr::Owned(_) => unreachable!("into_ref is only called on a borrowed GenRef"),
+        }
+    }
+}
+
+impl<'a, T, M: ProbablyMutable> Deref for GenRef<'a, T, M> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match &self.repr {
+            Repr::Ref(r) => r,
+            Repr::Mut(r) => r,
+            Repr::Owned(b) => b,
+        }
+    }
+}
+
+impl<'a, T> DerefMut for GenRef<'a, T, Mutable> {
+    fn deref_mut(&mut self) -> &mut T {
+        match &mut self.repr {
+            Repr::Mut(r) => r,
+            Repr::Ref(_) | Repr::Owned(_) => {
+                unreachable!("GenRef<Mutable> is only ever built from_mut")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derefs_through_ref_and_mut_variants() {
+        let value = 1;
+        let immutable = GenRef::<_, Immutable>::from_ref(&value);
+        assert_eq!(*immutable, 1);
+
+        let mut value = 2;
+        let mutable = GenRef::<_, Mutable>::from_mut(&mut value);
+        assert_eq!(*mutable, 2);
+    }
+
+    #[test]
+    fn deref_mut_writes_through_to_the_original() {
+        let mut value = 1;
+        let mut mutable = GenRef::<_, Mutable>::from_mut(&mut value);
+        *mutable = 42;
+        drop(mutable);
+        assert_eq!(value, 42);
+    }
+
+    #[test]
+    fn reborrow_yields_an_independent_immutable_view() {
+        let mut value = 7;
+        let mutable = GenRef::<_, Mutable>::from_mut(&mut value);
+        let reborrowed = mutable.reborrow();
+        assert_eq!(*reborrowed, 7);
+    }
+
+    #[test]
+    fn into_immutable_keeps_the_same_underlying_borrow() {
+        let mut value = 3;
+        let mutable = GenRef::<_, Mutable>::from_mut(&mut value);
+        let immutable = mutable.into_immutable();
+        assert_eq!(*immutable, 3);
+    }
+
+    #[test]
+    fn into_ref_preserves_the_original_lifetime() {
+        let value = 9;
+        let gen_ref = GenRef::<_, Immutable>::from_ref(&value);
+        let plain: &i32 = gen_ref.into_ref();
+        assert_eq!(*plain, 9);
+    }
+}
@@ -140,10 +140,25 @@
 //!     fn make_button(&mut self) -> WindowButton<Mutable> {todo!()}
 //! }
 //! ```
+//!
+//! Typing the same impl header three times gets old fast, so the
+//! `generic_mutability_macros` crate provides `#[generic_mutability]`, an attribute
+//! that sits on a single `impl` block and fans its methods out into the three
+//! blocks above, sorting by an inner `#[mutable]` / `#[immutable]` marker (unmarked
+//! methods stay in the generic block). [Window] and [Frame] below are written this
+//! way.
+use std::collections::HashMap;
 use std::marker::PhantomData;
 
+use generic_mutability_macros::generic_mutability;
 use log::debug;
 
+mod gen_ref;
+use gen_ref::GenRef;
+
+mod cell_root;
+use cell_root::CellRoot;
+
 mod monkey_ffi {
     //! module that imitates some FFI functions set.
     //!
@@ -267,14 +282,14 @@ impl Root {
     }
     fn make_child(&mut self) -> Window<Mutable> {
         let id = monkey_ffi::make_window();
-        let child = Window::new(self, id).expect("Should be initialized.");
+        let child = Window::new(GenRef::from_mut(self), id).expect("Should be initialized.");
         child
     }
     fn get_child(&self, id: usize) -> Option<Window<Immutable>> {
-        Window::new(self, id)
+        Window::new(GenRef::from_ref(self), id)
     }
     fn get_child_mut(&mut self, id: usize) -> Option<Window<Mutable>> {
-        Window::new(self, id)
+        Window::new(GenRef::from_mut(self), id)
     }
 }
 
@@ -283,11 +298,12 @@ struct Window<'a, T: ProbablyMutable> {
     name: String,
     frames_amount: usize,
     buttons_amount: usize,
-    root: &'a Root,
+    root: GenRef<'a, Root, T>,
     mutability: PhantomData<T>,
 }
+#[generic_mutability]
 impl<'a, T: ProbablyMutable> Window<'a, T> {
-    fn new(root: &'a Root, id: usize) -> Option<Self> {
+    fn new(root: GenRef<'a, Root, T>, id: usize) -> Option<Self> {
         match monkey_ffi::get_window(id) {
             0 => None,
             x => Self {
@@ -311,43 +327,101 @@ impl<'a, T: ProbablyMutable> Window<'a, T> {
         debug!("Some FFI call to het width");
         400
     }
-}
-impl<'a> Window<'a, Immutable> {
-    fn get_frame(&self, id: usize) -> Option<Frame<Immutable>> {
-        Frame::new(self, id)
+
+    #[immutable]
+    fn get_frame(&self, id: usize) -> Option<Frame<'_, 'a, Immutable>> {
+        Frame::new(GenRef::from_ref(self), id)
     }
-    fn get_button(&self, id: usize) -> Option<WindowButton<Immutable>> {
-        Button::new(self, id)
+    #[immutable]
+    fn get_button(&self, id: usize) -> Option<WindowButton<'_, 'a, Immutable>> {
+        WindowButton::new(GenRef::from_ref(self), id)
     }
-}
-impl<'a> Window<'a, Mutable> {
+
+    #[mutable]
     fn set_name(&mut self, name: impl Into<String>) {
         self.name = name.into();
     }
-    fn make_frame(&mut self) -> Frame<Mutable> {
+    #[mutable]
+    fn make_frame(&mut self) -> Frame<'_, 'a, Mutable> {
         debug!("Some FFI magic");
         let id = monkey_ffi::make_frame(self.get_id());
         self.frames_amount += 1;
-        let sub_child = Frame::new(self, id).expect("Should be created and valid.");
+        let sub_child =
+            Frame::new(GenRef::from_mut(self), id).expect("Should be created and valid.");
         sub_child
     }
-    fn make_button(&mut self) -> WindowButton<Mutable> {
+    #[mutable]
+    fn make_button(&mut self) -> WindowButton<'_, 'a, Mutable> {
         debug!("Some FFI magic");
         let id = self.buttons_amount;
         self.buttons_amount += 1;
-        let button = WindowButton::new(&*self, id).expect("Should be created and valid.");
+        let button =
+            WindowButton::new(GenRef::from_mut(self), id).expect("Should be created and valid.");
         button
     }
 }
+impl<'a> Window<'a, Mutable> {
+    /// Projects this mutable handle into an [Immutable] view without re-fetching
+    /// it from [Root], reusing the same borrow for as long as the view lives.
+    fn as_immutable(&self) -> Window<'_, Immutable> {
+        Window {
+            id: self.id,
+            name: self.name.clone(),
+            frames_amount: self.frames_amount,
+            buttons_amount: self.buttons_amount,
+            root: self.root.reborrow(),
+            mutability: PhantomData,
+        }
+    }
+
+    /// Consumes the mutable handle and hands back an owned-lifetime [Immutable] one.
+    fn into_immutable(self) -> Window<'a, Immutable> {
+        Window {
+            id: self.id,
+            name: self.name,
+            frames_amount: self.frames_amount,
+            buttons_amount: self.buttons_amount,
+            root: self.root.into_immutable(),
+            mutability: PhantomData,
+        }
+    }
+}
+impl<'a> Window<'a, Immutable> {
+    /// Collects this window's own buttons (not its frames' buttons) into a
+    /// heterogeneous collection, keyed by id.
+    fn children(&self) -> Children<'_, Immutable> {
+        let mut buttons: HashMap<usize, Box<dyn Button<Immutable> + '_>> = HashMap::new();
+        for id in 0..self.buttons_amount {
+            if let Some(button) = self.get_button(id) {
+                buttons.insert(id, Box::new(button));
+            }
+        }
+        Children { buttons }
+    }
+
+    /// Depth-first walk over every button reachable from this window: its own
+    /// buttons first, then each of its frames' buttons.
+    fn walk(&self, visitor: &mut dyn FnMut(&dyn Button<Immutable>)) {
+        for button in self.children().iter() {
+            visitor(button);
+        }
+        for id in 0..self.frames_amount {
+            if let Some(frame) = self.get_frame(id) {
+                frame.walk(visitor);
+            }
+        }
+    }
+}
 
-struct Frame<'a, T: ProbablyMutable> {
-    window: &'a Window<'a, T>,
+struct Frame<'a, 'w, T: ProbablyMutable> {
+    window: GenRef<'a, Window<'w, T>, T>,
     id: usize,
     width_px: Option<u16>,
     buttons_amount: usize,
 }
-impl<'a, T: ProbablyMutable> Frame<'a, T> {
-    fn new(window: &'a Window<T>, id: usize) -> Option<Self> {
+#[generic_mutability]
+impl<'a, 'w, T: ProbablyMutable> Frame<'a, 'w, T> {
+    fn new(window: GenRef<'a, Window<'w, T>, T>, id: usize) -> Option<Self> {
         let id = monkey_ffi::make_frame(id);
         match id {
             0 => None,
@@ -369,31 +443,84 @@ impl<'a, T: ProbablyMutable> Frame<'a, T> {
             Some(width) => width,
         }
     }
-}
-impl<'a> Frame<'a, Immutable> {
-    fn get_button(&self, id: usize) -> Option<FrameButton<Immutable>> {
-        FrameButton::new(self, id)
+
+    #[immutable]
+    fn get_button(&self, id: usize) -> Option<FrameButton<'_, 'a, 'w, Immutable>> {
+        FrameButton::new(GenRef::from_ref(self), id)
     }
-}
-impl<'a> Frame<'a, Mutable> {
+
+    #[mutable]
     fn set_width(&mut self, width_px: impl Into<u16>) {
         self.width_px = Some(width_px.into())
     }
-    fn make_button(&mut self) -> FrameButton<Mutable> {
+    #[mutable]
+    fn make_button(&mut self) -> FrameButton<'_, 'a, 'w, Mutable> {
         debug!("Some FFI magic");
         let id = self.buttons_amount;
         self.buttons_amount += 1;
-        let button = FrameButton::new(&*self, id).expect("Should be created and valid.");
+        let button =
+            FrameButton::new(GenRef::from_mut(self), id).expect("Should be created and valid.");
         button
     }
 }
+impl<'a, 'w> Frame<'a, 'w, Mutable> {
+    /// Projects this mutable handle into an [Immutable] view without re-fetching
+    /// it from [Window], reusing the same borrow for as long as the view lives.
+    fn as_immutable(&self) -> Frame<'_, '_, Immutable> {
+        Frame {
+            window: GenRef::from_owned(self.window.as_immutable()),
+            id: self.id,
+            width_px: self.width_px,
+            buttons_amount: self.buttons_amount,
+        }
+    }
+
+    /// Consumes the mutable handle and hands back an owned-lifetime [Immutable]
+    /// one. Moves `window` out of `self` instead of reborrowing through
+    /// `as_immutable`, since the latter's result can't outlive this function.
+    fn into_immutable(self) -> Frame<'a, 'a, Immutable> {
+        Frame {
+            window: GenRef::from_owned(self.window.into_ref().as_immutable()),
+            id: self.id,
+            width_px: self.width_px,
+            buttons_amount: self.buttons_amount,
+        }
+    }
+}
+impl<'a, 'w> Frame<'a, 'w, Immutable> {
+    /// Collects this frame's buttons into a heterogeneous collection, keyed by id.
+    fn children(&self) -> Children<'_, Immutable> {
+        let mut buttons: HashMap<usize, Box<dyn Button<Immutable> + '_>> = HashMap::new();
+        for id in 0..self.buttons_amount {
+            if let Some(button) = self.get_button(id) {
+                buttons.insert(id, Box::new(button));
+            }
+        }
+        Children { buttons }
+    }
 
-trait Button<T: ProbablyMutable>
+    /// Depth-first walk over every button reachable from this frame.
+    fn walk(&self, visitor: &mut dyn FnMut(&dyn Button<Immutable>)) {
+        for button in self.children().iter() {
+            visitor(button);
+        }
+    }
+}
+
+/// Constructs a [Button], given its parent handle.
+///
+/// Split out from [Button] itself so `Button<T>` stays object-safe: a
+/// `fn new(...) -> Self` return type (and the `Self: Sized` it requires) would
+/// otherwise rule out `Box<dyn Button<T>>`, which the heterogeneous children
+/// collections below need.
+trait ButtonNew<T: ProbablyMutable>
 where
     Self: Sized,
 {
     type Parent;
     fn new(parent: Self::Parent, id: usize) -> Option<Self>;
+}
+trait Button<T: ProbablyMutable> {
     fn get_id(&self) -> usize;
     fn is_clicked(&self) -> bool;
     fn get_text(&self) -> &String;
@@ -407,13 +534,34 @@ where
     fn set_text(&mut self, text: impl Into<String>);
 }
 
-struct WindowButton<'a, T: ProbablyMutable> {
+/// A heterogeneous, id-keyed collection of a [Window]'s or [Frame]'s buttons,
+/// stored behind the object-safe [Button] trait so `WindowButton` and
+/// `FrameButton` can be iterated and dispatched uniformly.
+///
+/// Neither `Window` nor `Frame` keep their buttons around between calls (they
+/// build them on demand from an id, like the rest of this crate's FFI
+/// wrappers), so `Children` builds the map eagerly from the `buttons_amount`
+/// ids known to be live.
+struct Children<'a, T: ProbablyMutable> {
+    buttons: HashMap<usize, Box<dyn Button<T> + 'a>>,
+}
+impl<'a, T: ProbablyMutable> Children<'a, T> {
+    fn iter(&self) -> impl Iterator<Item = &(dyn Button<T> + 'a)> {
+        self.buttons.values().map(Box::as_ref)
+    }
+
+    fn get(&self, id: usize) -> Option<&(dyn Button<T> + 'a)> {
+        self.buttons.get(&id).map(Box::as_ref)
+    }
+}
+
+struct WindowButton<'a, 'w, T: ProbablyMutable> {
     id: usize,
     text: String,
-    parent: &'a Window<'a, T>,
+    parent: GenRef<'a, Window<'w, T>, T>,
 }
-impl<'a, T: ProbablyMutable> Button<T> for WindowButton<'a, T> {
-    type Parent = &'a Window<'a, T>;
+impl<'a, 'w, T: ProbablyMutable> ButtonNew<T> for WindowButton<'a, 'w, T> {
+    type Parent = GenRef<'a, Window<'w, T>, T>;
 
     fn new(parent: Self::Parent, id: usize) -> Option<Self> {
         let id = monkey_ffi::make_window_button(id);
@@ -427,6 +575,8 @@ impl<'a, T: ProbablyMutable> Button<T> for WindowButton<'a, T> {
             .into(),
         }
     }
+}
+impl<'a, 'w, T: ProbablyMutable> Button<T> for WindowButton<'a, 'w, T> {
     fn get_id(&self) -> usize {
         self.id
     }
@@ -439,8 +589,8 @@ impl<'a, T: ProbablyMutable> Button<T> for WindowButton<'a, T> {
         &self.text
     }
 }
-impl<'a> ButtonMut for WindowButton<'a, Mutable> {
-    type Parent = Window<'a, Mutable>;
+impl<'a, 'w> ButtonMut for WindowButton<'a, 'w, Mutable> {
+    type Parent = Window<'w, Mutable>;
 
     fn click(&mut self) {
         monkey_ffi::window_button_click(self.parent.get_id(), self.id)
@@ -451,14 +601,36 @@ impl<'a> ButtonMut for WindowButton<'a, Mutable> {
         monkey_ffi::window_button_set_text(self.parent.get_id(), self.id, &self.text);
     }
 }
+impl<'a, 'w> WindowButton<'a, 'w, Mutable> {
+    /// Projects this mutable handle into an [Immutable] view without re-fetching
+    /// it from [Window], reusing the same borrow for as long as the view lives.
+    fn as_immutable(&self) -> WindowButton<'_, '_, Immutable> {
+        WindowButton {
+            id: self.id,
+            text: self.text.clone(),
+            parent: GenRef::from_owned(self.parent.as_immutable()),
+        }
+    }
+
+    /// Consumes the mutable handle and hands back an owned-lifetime [Immutable]
+    /// one. Moves `parent` out of `self` instead of reborrowing through
+    /// `as_immutable`, since the latter's result can't outlive this function.
+    fn into_immutable(self) -> WindowButton<'a, 'a, Immutable> {
+        WindowButton {
+            id: self.id,
+            text: self.text,
+            parent: GenRef::from_owned(self.parent.into_ref().as_immutable()),
+        }
+    }
+}
 
-struct FrameButton<'a, T: ProbablyMutable> {
+struct FrameButton<'a, 'f, 'w, T: ProbablyMutable> {
     id: usize,
     text: String,
-    parent: &'a Frame<'a, T>,
+    parent: GenRef<'a, Frame<'f, 'w, T>, T>,
 }
-impl<'a, T: ProbablyMutable> Button<T> for FrameButton<'a, T> {
-    type Parent = &'a Frame<'a, T>;
+impl<'a, 'f, 'w, T: ProbablyMutable> ButtonNew<T> for FrameButton<'a, 'f, 'w, T> {
+    type Parent = GenRef<'a, Frame<'f, 'w, T>, T>;
 
     fn new(parent: Self::Parent, id: usize) -> Option<Self> {
         let id = monkey_ffi::make_frame_button(id);
@@ -472,6 +644,8 @@ impl<'a, T: ProbablyMutable> Button<T> for FrameButton<'a, T> {
             .into(),
         }
     }
+}
+impl<'a, 'f, 'w, T: ProbablyMutable> Button<T> for FrameButton<'a, 'f, 'w, T> {
     fn get_id(&self) -> usize {
         self.id
     }
@@ -484,8 +658,8 @@ impl<'a, T: ProbablyMutable> Button<T> for FrameButton<'a, T> {
         &self.text
     }
 }
-impl<'a> ButtonMut for FrameButton<'a, Mutable> {
-    type Parent = Frame<'a, Mutable>;
+impl<'a, 'f, 'w> ButtonMut for FrameButton<'a, 'f, 'w, Mutable> {
+    type Parent = Frame<'f, 'w, Mutable>;
 
     fn click(&mut self) {
         monkey_ffi::frame_button_click(self.parent.get_id(), self.id)
@@ -496,6 +670,28 @@ impl<'a> ButtonMut for FrameButton<'a, Mutable> {
         monkey_ffi::frame_button_set_text(self.parent.get_id(), self.id, &self.text);
     }
 }
+impl<'a, 'f, 'w> FrameButton<'a, 'f, 'w, Mutable> {
+    /// Projects this mutable handle into an [Immutable] view without re-fetching
+    /// it from [Frame], reusing the same borrow for as long as the view lives.
+    fn as_immutable(&self) -> FrameButton<'_, '_, '_, Immutable> {
+        FrameButton {
+            id: self.id,
+            text: self.text.clone(),
+            parent: GenRef::from_owned(self.parent.as_immutable()),
+        }
+    }
+
+    /// Consumes the mutable handle and hands back an owned-lifetime [Immutable]
+    /// one. Moves `parent` out of `self` instead of reborrowing through
+    /// `as_immutable`, since the latter's result can't outlive this function.
+    fn into_immutable(self) -> FrameButton<'a, 'a, 'a, Immutable> {
+        FrameButton {
+            id: self.id,
+            text: self.text,
+            parent: GenRef::from_owned(self.parent.into_ref().as_immutable()),
+        }
+    }
+}
 
 fn main() {
     env_logger::init();
@@ -518,21 +714,56 @@ fn main() {
     let mut window1 = root.get_child_mut(w1_id).unwrap();
     let button = window1.make_button();
     let b_id = button.get_id();
-    let mut frame = window1.make_frame();
-    let fr_b_id = frame.make_button().get_id();
-    let f_id = frame.get_id();
-    // Err: cannot borrow `window1` as mutable more than once at a time
-    // debug!("button text: {}", button.get_text());
-
-    // Err: no method named `get_button` found for struct
-    // `Window<'_, test::Mutable>` in the current scope
-    // the method was found for - `Window<'a, test::Immutable>`
-    // let button = window1.get_button(b_id);
-    let window1 = root.get_child(w1_id).unwrap();
-    let frame = window1.get_frame(f_id).unwrap();
-    let w_b = window1.get_button(b_id).unwrap();
-    let fr_b = frame.get_button(fr_b_id).unwrap();
+    // `as_immutable` reborrows `button` in place, so there's no more need to drop
+    // it and re-fetch it through `window1`/`root` just to read its text back.
+    debug!("button text: {}", button.as_immutable().get_text());
+    // `into_immutable` does the same, but consumes the mutable handle instead of
+    // reborrowing it, for when the mutable handle isn't needed anymore.
+    let button = button.into_immutable();
+    debug!("button id via into_immutable: {}", button.get_id());
 
+    let window1_view = window1.as_immutable();
+    let w_b = window1_view.get_button(b_id).unwrap();
     debug!("is window button clicked: {}", w_b.is_clicked());
+
+    let mut frame = window1.make_frame();
+    let fr_button = frame.make_button();
+    let fr_b_id = fr_button.get_id();
+    debug!("frame button text: {}", fr_button.as_immutable().get_text());
+    let fr_button = fr_button.into_immutable();
+    debug!("frame button id via into_immutable: {}", fr_button.get_id());
+
+    let frame_view = frame.as_immutable();
+    let fr_b = frame_view.get_button(fr_b_id).unwrap();
     debug!("is frame button clicked: {}", fr_b.is_clicked());
+
+    let frame = frame.into_immutable();
+    debug!("frame width: {}", frame.get_width());
+
+    // `into_immutable` does the same, but consumes the mutable handle instead of
+    // reborrowing it, for when the mutable handle isn't needed anymore.
+    let window1 = window1.into_immutable();
+    debug!("window name: {}", window1.get_name());
+
+    // `children`/`walk` give real tree enumeration instead of id-guessing: no
+    // need to already know `b_id` to find this button again. `Children` is keyed
+    // by the window's own button index, so the first button made above is at 0.
+    if let Some(found) = window1.children().get(0) {
+        debug!("found button {} via Children::get", found.get_id());
+    }
+    let mut button_count = 0;
+    window1.walk(&mut |_button| button_count += 1);
+    debug!("buttons reachable from window1: {}", button_count);
+
+    // `CellRoot` is the runtime-checked alternative to `Root`: it allows several
+    // `Immutable` handles to the same window to coexist, at the cost of panicking
+    // on an aliasing violation instead of having the borrow checker reject it.
+    let cell_root = CellRoot::new();
+    let mut cell_window = cell_root.make_child();
+    cell_window.set_name("cell window");
+    let id = cell_window.get_id();
+    drop(cell_window);
+    let view_a = cell_root.get_child(id).unwrap();
+    let view_b = cell_root.get_child(id).unwrap(); // OK: many `Immutable` handles at once
+    debug!("{} / {}", view_a.get_name(), view_b.get_name());
 }